@@ -1,12 +1,20 @@
+use std::fs::File;
+use std::path::PathBuf;
+
 use serde::Deserialize;
 
-use crate::{DaError, DaLayer, DaMode};
+use crate::utils::resolve_env_vars;
+use crate::{DaError, DaMode};
 
 const DEFAULT_AVAIL_WS: &str = "ws://127.0.0.1:9945";
 const DEFAULT_APP_ID: u32 = 0;
 const DEFAULT_AVAIL_VALIDATE_CODEGEN: bool = false;
 const DEFAULT_AVAIL_SEED: &str = "//Alice";
 
+/// Avail DA configuration
+///
+/// `seed` may reference environment variables as `${ENV_VAR}`, they are resolved when the config
+/// is loaded from a file.
 #[derive(Clone, PartialEq, Deserialize, Debug)]
 pub struct AvailConfig {
     #[serde(default = "default_ws")]
@@ -22,10 +30,15 @@ pub struct AvailConfig {
 }
 
 impl TryFrom<&PathBuf> for AvailConfig {
-    type Error = DaErr;
+    type Error = DaError;
+
     fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
-        let file = File::open(path).map_err(|e| DaError::FailedOpeningConfig(e))?;
-        serde_json::from_reader(file).map_err(|e| DaError::FailedParsingConfig(e))
+        let file = File::open(path).map_err(DaError::FailedOpeningConfig)?;
+        let mut conf: Self = serde_json::from_reader(file).map_err(DaError::FailedParsingConfig)?;
+
+        conf.seed = resolve_env_vars(&conf.seed)?;
+
+        Ok(conf)
     }
 }
 
@@ -56,3 +69,20 @@ impl Default for AvailConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_env_var_is_resolved() {
+        std::env::set_var("MADARA_DA_TEST_AVAIL_SEED", "//Bob");
+        let path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{ "seed": "${MADARA_DA_TEST_AVAIL_SEED}" }"#).unwrap();
+
+        let conf = AvailConfig::try_from(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(conf.unwrap().seed, "//Bob");
+    }
+}
@@ -3,6 +3,7 @@ pub mod config;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use anyhow::Result;
 use async_trait::async_trait;
 use avail_subxt::api::runtime_types::avail_core::AppId;
 use avail_subxt::api::runtime_types::bounded_collections::bounded_vec::BoundedVec;
@@ -15,7 +16,7 @@ use subxt::ext::sp_core::sr25519::Pair;
 use subxt::OnlineClient;
 
 use crate::utils::get_bytes_from_state_diff;
-use crate::{DaClient, DaError, DaLayer, DaMode};
+use crate::{unsupported_by_da_layer, DaClient, DaError, DaMode};
 
 type AvailPairSigner = subxt::tx::PairSigner<AvailConfig, Pair>;
 
@@ -44,7 +45,7 @@ impl SubxtClient {
     pub async fn restart(&mut self) -> Result<(), DaError> {
         self.client = match build_client(self.config.ws_provider.as_str(), self.config.validate_codegen).await {
             Ok(i) => i,
-            Err(e) => return DaError::FailedBuildingClient(e.into()),
+            Err(e) => return Err(DaError::FailedBuildingClient(e.into())),
         };
 
         Ok(())
@@ -65,18 +66,16 @@ impl TryFrom<config::AvailConfig> for SubxtClient {
 
 #[async_trait]
 impl DaClient for AvailClient {
-    async fn publish_state_diff(&self, state_diff: Vec<U256>) -> Result<(), DaError> {
-        let bytes = get_bytes_from_state_diff(&state_diff);
-        let bytes = BoundedVec(bytes);
-        self.publish_data(&bytes).await?;
+    async fn publish_state_diff(&self, state_diff: Vec<U256>) -> Result<()> {
+        self.publish_data(&state_diff_payload(&state_diff)).await?;
 
         Ok(())
     }
 
-    // state diff can be published w/o verification of last state for the time being
-    // may change in subsequent DaMode implementations
+    // submitted data doesn't record the Madara block it belongs to, so the last published
+    // state can't be read back from Avail
     async fn last_published_state(&self) -> Result<I256> {
-        Ok(I256::from(1))
+        Err(unsupported_by_da_layer("reading the last published state"))
     }
 
     fn get_mode(&self) -> DaMode {
@@ -102,7 +101,7 @@ impl AvailClient {
                     let _ = ws_client.restart().await;
                 }
 
-                return DaError::FailedBuildingClient(e.into());
+                return Err(DaError::FailedDataSubmission(e.into()));
             }
         };
 
@@ -127,6 +126,11 @@ impl TryFrom<config::AvailConfig> for AvailClient {
     }
 }
 
+/// Encodes the state diff as the `submit_data` payload, one 32-byte big-endian word per element
+fn state_diff_payload(state_diff: &[U256]) -> BoundedVec<u8> {
+    BoundedVec(get_bytes_from_state_diff(state_diff))
+}
+
 fn signer_from_seed(seed: &str) -> Result<AvailPairSigner, DaError> {
     let pair = <Pair as subxt::ext::sp_core::Pair>::from_string(seed, None)
         .map_err(|e| DaError::FailedConversion(e.into()))?;
@@ -134,3 +138,23 @@ fn signer_from_seed(seed: &str) -> Result<AvailPairSigner, DaError> {
     let signer = AvailPairSigner::new(pair);
     Ok(signer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_diff_payload_encodes_each_word() {
+        let payload = state_diff_payload(&[U256::one(), U256::MAX]);
+
+        let mut expected = vec![0u8; 31];
+        expected.push(1);
+        expected.extend([0xff; 32]);
+        assert_eq!(payload.0, expected);
+    }
+
+    #[test]
+    fn state_diff_payload_of_empty_diff_is_empty() {
+        assert!(state_diff_payload(&[]).0.is_empty());
+    }
+}
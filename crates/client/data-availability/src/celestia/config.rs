@@ -1,10 +1,18 @@
+use std::fs::File;
+use std::path::PathBuf;
+
 use serde::Deserialize;
 
+use crate::utils::resolve_env_vars;
 use crate::{DaError, DaMode};
 
 pub const DEFAULT_CELESTIA_NODE: &str = "127.0.0.1:26658";
 pub const DEFAULT_NID: &str = "Madara";
 
+/// Celestia DA configuration
+///
+/// `auth_token` may reference environment variables as `${ENV_VAR}`, they are resolved when the
+/// config is loaded from a file.
 #[derive(Clone, PartialEq, Deserialize, Debug)]
 pub struct CelestiaConfig {
     #[serde(default = "default_http")]
@@ -23,8 +31,12 @@ impl TryFrom<&PathBuf> for CelestiaConfig {
     type Error = DaError;
 
     fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
-        let file = File::open(path).map_err(|e| DaError::FailedOpeningConfig(e))?;
-        serde_json::from_reader(file).map_err(|e| DaError::FailedParsingConfig(e))
+        let file = File::open(path).map_err(DaError::FailedOpeningConfig)?;
+        let mut conf: Self = serde_json::from_reader(file).map_err(DaError::FailedParsingConfig)?;
+
+        conf.auth_token = conf.auth_token.as_deref().map(resolve_env_vars).transpose()?;
+
+        Ok(conf)
     }
 }
 
@@ -51,3 +63,20 @@ impl Default for CelestiaConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auth_token_env_var_is_resolved() {
+        std::env::set_var("MADARA_DA_TEST_CELESTIA_AUTH_TOKEN", "secret");
+        let path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{ "auth_token": "${MADARA_DA_TEST_CELESTIA_AUTH_TOKEN}" }"#).unwrap();
+
+        let conf = CelestiaConfig::try_from(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(conf.unwrap().auth_token.as_deref(), Some("secret"));
+    }
+}
@@ -12,7 +12,7 @@ use ethers::types::{I256, U256};
 use jsonrpsee::http_client::{HeaderMap, HeaderValue, HttpClient, HttpClientBuilder};
 use reqwest::header;
 
-use crate::{DaClient, DaError, DaMode};
+use crate::{unsupported_by_da_layer, DaClient, DaError, DaMode};
 
 #[derive(Clone, Debug)]
 pub struct CelestiaClient {
@@ -23,7 +23,7 @@ pub struct CelestiaClient {
 
 #[async_trait]
 impl DaClient for CelestiaClient {
-    async fn publish_state_diff(&self, state_diff: Vec<U256>) -> Result<(), DaError> {
+    async fn publish_state_diff(&self, state_diff: Vec<U256>) -> Result<()> {
         let blob = self.get_blob_from_state_diff(state_diff).map_err(|e| DaError::FailedDataFetching(e.into()))?;
 
         let submitted_height = self.publish_data(&blob).await.map_err(|e| DaError::FailedDataSubmission(e.into()))?;
@@ -39,8 +39,10 @@ impl DaClient for CelestiaClient {
         Ok(())
     }
 
+    // published blobs don't record the Madara block they belong to, so the last published
+    // state can't be read back from Celestia
     async fn last_published_state(&self) -> Result<I256> {
-        Ok(I256::from(1))
+        Err(unsupported_by_da_layer("reading the last published state"))
     }
 
     fn get_mode(&self) -> DaMode {
@@ -93,7 +95,8 @@ impl TryFrom<config::CelestiaConfig> for CelestiaClient {
         // we only need to initiate the http provider and not the ws provider, we don't need async
         let mut headers = HeaderMap::new();
         if let Some(auth_token) = conf.auth_token {
-            let val = HeaderValue::from_str(&format!("Bearer {}", auth_token))?;
+            let val = HeaderValue::from_str(&format!("Bearer {}", auth_token))
+                .map_err(|e| DaError::FailedBuildingClient(e.into()))?;
             headers.insert(header::AUTHORIZATION, val);
         }
 
@@ -111,3 +114,51 @@ impl TryFrom<config::CelestiaConfig> for CelestiaClient {
         Ok(Self { http_client, nid, mode: conf.mode })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use jsonrpsee::server::{RpcModule, Server};
+    use jsonrpsee::types::ErrorObjectOwned;
+
+    use super::*;
+    use crate::celestia::config::{CelestiaConfig, DEFAULT_NID};
+    use crate::utils::get_bytes_from_state_diff;
+
+    #[tokio::test]
+    async fn last_published_state_and_block_number_are_not_supported() {
+        let da_client = CelestiaClient::try_from(CelestiaConfig::default()).unwrap();
+
+        assert!(da_client.last_published_state().await.is_err());
+        assert!(da_client.last_published_block_number().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn publish_state_diff_submits_blob() {
+        let submitted = Arc::new(Mutex::new(Vec::<Blob>::new()));
+        let server = Server::builder().build("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let mut module = RpcModule::new(submitted.clone());
+        module
+            .register_method("blob.Submit", |params, submitted| {
+                let (blobs, _options): (Vec<Blob>, serde_json::Value) = params.parse()?;
+                submitted.lock().unwrap().extend(blobs);
+                Ok::<_, ErrorObjectOwned>(1_u64)
+            })
+            .unwrap();
+        let _handle = server.start(module);
+
+        let conf = CelestiaConfig { http_provider: format!("http://{addr}"), ..Default::default() };
+        let da_client = CelestiaClient::try_from(conf).unwrap();
+        let state_diff = vec![U256::one(), U256::MAX];
+
+        // only the submission is mocked, waiting for its inclusion fails
+        assert!(da_client.publish_state_diff(state_diff.clone()).await.is_err());
+
+        let submitted = submitted.lock().unwrap();
+        assert_eq!(submitted.len(), 1);
+        assert_eq!(submitted[0].namespace, Namespace::new_v0(DEFAULT_NID.as_bytes()).unwrap());
+        assert_eq!(submitted[0].data, get_bytes_from_state_diff(&state_diff));
+    }
+}
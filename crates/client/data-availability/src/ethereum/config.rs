@@ -1,8 +1,10 @@
 use std::fs::File;
 use std::path::PathBuf;
 
-use serde::{Deserialize, Serialize};
+use ethers::types::BlockNumber;
+use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::utils::resolve_env_vars;
 use crate::{DaError, DaMode};
 
 pub const DEFAULT_ETHEREUM_NODE: &str = "127.0.0.1:8545";
@@ -13,6 +15,10 @@ pub const DEFAULT_SEQUENCER_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb47
 pub const DEFAULT_STARKNET_CORE_CONTRACTS: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
 pub const DEFAULT_CHAIN_ID: u64 = 31337;
 
+/// Ethereum DA configuration
+///
+/// `http_provider` and `sequencer_key` may reference environment variables as `${ENV_VAR}`,
+/// they are resolved when the config is loaded from a file.
 #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 pub struct EthereumConfig {
     #[serde(default = "default_http")]
@@ -27,6 +33,13 @@ pub struct EthereumConfig {
     pub mode: DaMode,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub poll_interval_ms: Option<u64>,
+    /// Max fee (in wei) a state update may cost, publishing fails if the estimate exceeds it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee: Option<u128>,
+    /// Block at which the last published state is read: a tag (`latest`, `pending`...), a block
+    /// number such as `42` or its hex string `"0x2a"`
+    #[serde(default, deserialize_with = "deserialize_block_number")]
+    pub last_published_state_block: BlockNumber,
 }
 
 impl TryFrom<&PathBuf> for EthereumConfig {
@@ -34,7 +47,12 @@ impl TryFrom<&PathBuf> for EthereumConfig {
 
     fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
         let file = File::open(path).map_err(DaError::FailedOpeningConfig)?;
-        serde_json::from_reader(file).map_err(DaError::FailedParsingConfig)
+        let mut conf: Self = serde_json::from_reader(file).map_err(DaError::FailedParsingConfig)?;
+
+        conf.http_provider = resolve_env_vars(&conf.http_provider)?;
+        conf.sequencer_key = resolve_env_vars(&conf.sequencer_key)?;
+
+        Ok(conf)
     }
 }
 
@@ -54,6 +72,21 @@ fn default_chain_id() -> u64 {
     DEFAULT_CHAIN_ID
 }
 
+// `BlockNumber` only deserializes tags and hex strings
+fn deserialize_block_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BlockNumber, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BlockNumberOrTag {
+        Number(u64),
+        Tag(BlockNumber),
+    }
+
+    Ok(match BlockNumberOrTag::deserialize(deserializer)? {
+        BlockNumberOrTag::Number(number) => BlockNumber::Number(number.into()),
+        BlockNumberOrTag::Tag(block) => block,
+    })
+}
+
 impl Default for EthereumConfig {
     fn default() -> Self {
         Self {
@@ -63,6 +96,50 @@ impl Default for EthereumConfig {
             sequencer_key: default_sequencer_key(),
             chain_id: default_chain_id(),
             poll_interval_ms: None,
+            max_fee: None,
+            last_published_state_block: BlockNumber::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_env_vars_are_resolved() {
+        std::env::set_var("MADARA_DA_TEST_ETHEREUM_API_KEY", "secret");
+        std::env::set_var("MADARA_DA_TEST_ETHEREUM_SEQUENCER_KEY", DEFAULT_SEQUENCER_KEY);
+        let path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{
+                "http_provider": "https://eth.node/v2/${MADARA_DA_TEST_ETHEREUM_API_KEY}",
+                "sequencer_key": "${MADARA_DA_TEST_ETHEREUM_SEQUENCER_KEY}"
+            }"#,
+        )
+        .unwrap();
+
+        let conf = EthereumConfig::try_from(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let conf = conf.unwrap();
+        assert_eq!(conf.http_provider, "https://eth.node/v2/secret");
+        assert_eq!(conf.sequencer_key, DEFAULT_SEQUENCER_KEY);
+    }
+
+    #[test]
+    fn last_published_state_block_defaults_to_latest() {
+        let conf: EthereumConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(conf.last_published_state_block, BlockNumber::Latest);
+
+        let conf: EthereumConfig = serde_json::from_str(r#"{ "last_published_state_block": "pending" }"#).unwrap();
+        assert_eq!(conf.last_published_state_block, BlockNumber::Pending);
+
+        let conf: EthereumConfig = serde_json::from_str(r#"{ "last_published_state_block": "0x2a" }"#).unwrap();
+        assert_eq!(conf.last_published_state_block, BlockNumber::Number(42.into()));
+
+        let conf: EthereumConfig = serde_json::from_str(r#"{ "last_published_state_block": 42 }"#).unwrap();
+        assert_eq!(conf.last_published_state_block, BlockNumber::Number(42.into()));
+    }
+}
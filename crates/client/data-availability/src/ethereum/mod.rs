@@ -4,50 +4,37 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use ethers::contract::ContractCall;
 use ethers::prelude::{abigen, SignerMiddleware};
-use ethers::providers::{Http, Provider};
+use ethers::providers::{Http, JsonRpcClient, Middleware, Provider};
 use ethers::signers::{LocalWallet, Signer};
-use ethers::types::{Address, I256, U256};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockNumber, I256, U256};
 
-use crate::utils::is_valid_http_endpoint;
+use crate::utils::{endpoint_host, get_valid_url, is_valid_http_endpoint};
 use crate::{DaClient, DaError, DaMode};
 
+type UpdateStateCall<P> = ContractCall<SignerMiddleware<Provider<P>, LocalWallet>, ()>;
+
 #[derive(Clone, Debug)]
-pub struct EthereumClient {
-    http_provider: Provider<Http>,
-    signer: Arc<SignerMiddleware<Provider<Http>, LocalWallet>>,
+pub struct EthereumClient<P: JsonRpcClient = Http> {
+    provider: Provider<P>,
+    signer: Arc<SignerMiddleware<Provider<P>, LocalWallet>>,
+    endpoint: String,
     cc_address: Address,
     mode: DaMode,
+    max_fee: Option<U256>,
+    last_published_state_block: BlockNumber,
 }
 
 #[async_trait]
-impl DaClient for EthereumClient {
+impl<P: JsonRpcClient + Clone + 'static> DaClient for EthereumClient<P> {
     async fn publish_state_diff(&self, state_diff: Vec<U256>) -> Result<(), anyhow::Error> {
         log::debug!("State Update: {:?}", state_diff);
-        let fmt_tx = match self.mode {
-            DaMode::Sovereign => {
-                abigen!(
-                    STARKNET,
-                    r#"[
-                        function updateState(uint256[] calldata programOutput) external
-                    ]"#,
-                );
-
-                let core_contracts = STARKNET::new(self.cc_address, self.signer.clone());
-                core_contracts.update_state(state_diff)
-            }
-            _ => {
-                abigen!(
-                    STARKNET,
-                    r#"[
-                        function updateState(uint256[] calldata programOutput, uint256 onchainDataHash, uint256 onchainDataSize) external
-                    ]"#,
-                );
+        let mut fmt_tx = self.update_state_call(state_diff);
 
-                let core_contracts = STARKNET::new(self.cc_address, self.signer.clone());
-                core_contracts.update_state(state_diff, U256::default(), U256::default())
-            }
-        };
+        let estimated_fee = self.estimate_fee(&mut fmt_tx).await?;
+        check_fee(estimated_fee, self.max_fee)?;
 
         let tx = fmt_tx
             .send()
@@ -68,16 +55,115 @@ impl DaClient for EthereumClient {
             ]"#,
         );
 
-        let contract = STARKNET::new(self.cc_address, self.http_provider.clone().into());
-        Ok(contract.state_block_number().call().await.map_err(|e| DaError::FailedDataSubmission(e.into()))?)
+        let contract = STARKNET::new(self.cc_address, self.provider.clone().into());
+        Ok(contract
+            .state_block_number()
+            .block(self.last_published_state_block)
+            .call()
+            .await
+            .map_err(|e| DaError::FailedDataFetching(e.into()))?)
     }
 
     fn get_mode(&self) -> DaMode {
         self.mode
     }
 
+    // Estimates the transaction `publish_state_diff` would send, `max_fee` is only enforced when
+    // publishing so the cost of a diff above it can still be reported
+    async fn estimate_publish_cost(&self, state_diff: &[U256]) -> Result<U256, anyhow::Error> {
+        let mut fmt_tx = self.update_state_call(state_diff.to_vec());
+
+        Ok(self.estimate_fee(&mut fmt_tx).await?)
+    }
+
     fn get_da_metric_labels(&self) -> HashMap<String, String> {
-        [("name".into(), "ethereum".into())].iter().cloned().collect()
+        [
+            ("name".into(), "ethereum".into()),
+            ("chain_id".into(), self.signer.signer().chain_id().to_string()),
+            ("endpoint".into(), self.endpoint.clone()),
+            ("core_contracts".into(), format!("{:#x}", self.cc_address)),
+        ]
+        .iter()
+        .cloned()
+        .collect()
+    }
+}
+
+impl<P: JsonRpcClient + Clone + 'static> EthereumClient<P> {
+    /// Builds a client sending its requests through `provider` instead of `conf.http_provider`,
+    /// which is then only used to label metrics
+    pub fn with_provider(provider: Provider<P>, conf: config::EthereumConfig) -> Result<Self, DaError> {
+        let wallet: LocalWallet = conf
+            .sequencer_key
+            .parse::<LocalWallet>()
+            .map_err(|e| DaError::FailedConversion(e.into()))?
+            .with_chain_id(conf.chain_id);
+
+        let signer = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+
+        let cc_address: Address =
+            conf.core_contracts.parse::<Address>().map_err(|e| DaError::FailedConversion(e.into()))?;
+
+        Ok(Self {
+            provider,
+            signer,
+            endpoint: get_valid_url(&conf.http_provider).map(|url| endpoint_host(&url)).unwrap_or_default(),
+            cc_address,
+            mode: conf.mode,
+            max_fee: conf.max_fee.map(U256::from),
+            last_published_state_block: conf.last_published_state_block,
+        })
+    }
+
+    fn update_state_call(&self, state_diff: Vec<U256>) -> UpdateStateCall<P> {
+        match self.mode {
+            DaMode::Sovereign => {
+                abigen!(
+                    STARKNET,
+                    r#"[
+                        function updateState(uint256[] calldata programOutput) external
+                    ]"#,
+                );
+
+                let core_contracts = STARKNET::new(self.cc_address, self.signer.clone());
+                core_contracts.update_state(state_diff)
+            }
+            _ => {
+                abigen!(
+                    STARKNET,
+                    r#"[
+                        function updateState(uint256[] calldata programOutput, uint256 onchainDataHash, uint256 onchainDataSize) external
+                    ]"#,
+                );
+
+                let core_contracts = STARKNET::new(self.cc_address, self.signer.clone());
+                core_contracts.update_state(state_diff, U256::default(), U256::default())
+            }
+        }
+    }
+
+    /// Sets the gas limit and EIP-1559 fees of `call` from the provider estimates
+    ///
+    /// Returns the highest fee the transaction may pay.
+    async fn estimate_fee(&self, call: &mut UpdateStateCall<P>) -> Result<U256, DaError> {
+        let estimated_gas = call.estimate_gas().await.map_err(|e| DaError::FailedDataSubmission(e.into()))?;
+        let (max_fee_per_gas, max_priority_fee_per_gas) =
+            self.provider.estimate_eip1559_fees(None).await.map_err(|e| DaError::FailedDataFetching(e.into()))?;
+
+        call.tx.set_gas(estimated_gas);
+        match &mut call.tx {
+            TypedTransaction::Eip1559(tx) => {
+                tx.max_fee_per_gas = Some(max_fee_per_gas);
+                tx.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+            }
+            tx => {
+                tx.set_gas_price(max_fee_per_gas);
+            }
+        }
+
+        log::debug!("State Update estimated gas: {estimated_gas} at most {max_fee_per_gas} wei per gas");
+
+        Ok(estimated_gas.saturating_mul(max_fee_per_gas))
     }
 }
 
@@ -89,20 +175,156 @@ impl TryFrom<config::EthereumConfig> for EthereumClient {
             return Err(DaError::InvalidHttpEndpoint(conf.http_provider));
         }
 
-        let provider =
-            Provider::<Http>::try_from(conf.http_provider).map_err(|e| DaError::FailedBuildingClient(e.into()))?;
+        let provider = Provider::<Http>::try_from(conf.http_provider.as_str())
+            .map_err(|e| DaError::FailedBuildingClient(e.into()))?;
 
-        let wallet: LocalWallet = conf
-            .sequencer_key
-            .parse::<LocalWallet>()
-            .map_err(|e| DaError::FailedConversion(e.into()))?
-            .with_chain_id(conf.chain_id);
+        Self::with_provider(provider, conf)
+    }
+}
 
-        let signer = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+/// Returns an error if the highest fee of a transaction is above `max_fee`
+fn check_fee(estimated_fee: U256, max_fee: Option<U256>) -> Result<(), DaError> {
+    match max_fee {
+        Some(max_fee) if estimated_fee > max_fee => Err(DaError::FeeAboveMax { estimated_fee, max_fee }),
+        _ => Ok(()),
+    }
+}
 
-        let cc_address: Address =
-            conf.core_contracts.parse::<Address>().map_err(|e| DaError::FailedConversion(e.into()))?;
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{encode, Token};
+    use ethers::providers::{MockError, MockProvider};
+    use ethers::types::{Block, Bytes, Eip1559TransactionRequest, FeeHistory, TxHash};
+    use ethers::utils::id;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::ethereum::config::{EthereumConfig, DEFAULT_CHAIN_ID, DEFAULT_STARKNET_CORE_CONTRACTS};
+
+    const GWEI: u64 = 1_000_000_000;
+
+    /// Mocks the responses to a fee estimate of `gas` at a 10 gwei base fee, which allows up to
+    /// 20 gwei per gas
+    fn push_fee_estimate(mock: &MockProvider, gas: u64) {
+        // responses are served last in first out
+        let fee_history =
+            FeeHistory { base_fee_per_gas: vec![], gas_used_ratio: vec![], oldest_block: U256::zero(), reward: vec![] };
+        mock.push(fee_history).unwrap();
+        mock.push(Block::<TxHash> { base_fee_per_gas: Some(U256::from(10 * GWEI)), ..Default::default() }).unwrap();
+        mock.push(U256::from(gas)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn estimate_publish_cost_is_the_publish_max_fee() {
+        let (provider, mock) = Provider::mocked();
+        let da_client = EthereumClient::with_provider(provider, EthereumConfig::default()).unwrap();
+        let state_diff = vec![U256::one()];
+        push_fee_estimate(&mock, 100_000);
+
+        assert_eq!(da_client.estimate_publish_cost(&state_diff).await.unwrap(), U256::from(2_000_000 * GWEI));
+
+        let mut tx = da_client.update_state_call(state_diff).tx;
+        tx.set_from(da_client.signer.address());
+        mock.assert_request("eth_estimateGas", [tx]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn estimate_publish_cost_is_reported_above_max_fee() {
+        let (provider, mock) = Provider::mocked();
+        let conf = EthereumConfig { max_fee: Some(1_000_000 * GWEI as u128), ..Default::default() };
+        let da_client = EthereumClient::with_provider(provider, conf).unwrap();
+        push_fee_estimate(&mock, 100_000);
+
+        assert_eq!(da_client.estimate_publish_cost(&[U256::one()]).await.unwrap(), U256::from(2_000_000 * GWEI));
+    }
+
+    #[tokio::test]
+    async fn estimate_publish_cost_estimates_the_whole_state_diff() {
+        let (provider, mock) = Provider::mocked();
+        let da_client = EthereumClient::with_provider(provider, EthereumConfig::default()).unwrap();
+        let (small_diff, large_diff) = (vec![U256::one()], vec![U256::one(); 10]);
+
+        let mut calldata_lens = vec![];
+        for state_diff in [small_diff, large_diff] {
+            push_fee_estimate(&mock, 100_000);
+            da_client.estimate_publish_cost(&state_diff).await.unwrap();
+
+            let mut tx = da_client.update_state_call(state_diff).tx;
+            tx.set_from(da_client.signer.address());
+            mock.assert_request("eth_estimateGas", [&tx]).unwrap();
+            mock.assert_request("eth_getBlockByNumber", ("latest", false)).unwrap();
+            mock.assert_request("eth_feeHistory", (U256::from(10), "latest", [5.0])).unwrap();
+            calldata_lens.push(tx.data().unwrap().len());
+        }
+
+        // each extra element of the diff adds a 32-byte word to the estimated calldata
+        assert_eq!(calldata_lens[1] - calldata_lens[0], 9 * 32);
+    }
+
+    #[tokio::test]
+    async fn publish_state_diff_fails_above_max_fee_without_sending() {
+        let (provider, mock) = Provider::mocked();
+        let conf = EthereumConfig { max_fee: Some(1_000_000 * GWEI as u128), ..Default::default() };
+        let da_client = EthereumClient::with_provider(provider, conf).unwrap();
+        let state_diff = vec![U256::one()];
+        push_fee_estimate(&mock, 100_000);
+
+        let err = da_client.publish_state_diff(state_diff.clone()).await.unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<DaError>(),
+            Some(DaError::FeeAboveMax { estimated_fee, .. }) if *estimated_fee == U256::from(2_000_000 * GWEI)
+        ));
+
+        let mut tx = da_client.update_state_call(state_diff).tx;
+        tx.set_from(da_client.signer.address());
+        mock.assert_request("eth_estimateGas", [tx]).unwrap();
+        mock.assert_request("eth_getBlockByNumber", ("latest", false)).unwrap();
+        mock.assert_request("eth_feeHistory", (U256::from(10), "latest", [5.0])).unwrap();
+        assert!(matches!(mock.assert_request("eth_sendRawTransaction", ()), Err(MockError::EmptyRequests)));
+    }
+
+    #[rstest]
+    #[case::pending(BlockNumber::Pending, "pending")]
+    #[case::fixed(BlockNumber::Number(42.into()), "0x2a")]
+    #[tokio::test]
+    async fn last_published_state_is_read_at_the_configured_block(#[case] block: BlockNumber, #[case] tag: &str) {
+        let (provider, mock) = Provider::mocked();
+        let conf = EthereumConfig { last_published_state_block: block, ..Default::default() };
+        let da_client = EthereumClient::with_provider(provider, conf).unwrap();
+        mock.push::<Bytes, Bytes>(encode(&[Token::Int(U256::from(7))]).into()).unwrap();
+
+        assert_eq!(da_client.last_published_state().await.unwrap(), I256::from(7));
+
+        let tx: TypedTransaction =
+            Eip1559TransactionRequest::new().to(da_client.cc_address).data(id("stateBlockNumber()").to_vec()).into();
+        mock.assert_request("eth_call", (tx, tag)).unwrap();
+    }
+
+    #[test]
+    fn da_metric_labels_identify_the_target() {
+        let conf = EthereumConfig {
+            http_provider: "https://eth.node/v2/secret-key".into(),
+            core_contracts: DEFAULT_STARKNET_CORE_CONTRACTS.to_lowercase(),
+            ..Default::default()
+        };
+        let labels = EthereumClient::try_from(conf).unwrap().get_da_metric_labels();
+
+        assert_eq!(labels["name"], "ethereum");
+        assert_eq!(labels["chain_id"], DEFAULT_CHAIN_ID.to_string());
+        assert_eq!(labels["endpoint"], "eth.node");
+        assert_eq!(labels["core_contracts"], DEFAULT_STARKNET_CORE_CONTRACTS.to_lowercase());
+    }
+
+    #[test]
+    fn check_fee_enforces_max_fee() {
+        let fee = U256::from(3_000_000_000_000_000_u64);
 
-        Ok(Self { http_provider: provider, signer, cc_address, mode: conf.mode })
+        assert!(check_fee(fee, None).is_ok());
+        assert!(check_fee(fee, Some(fee)).is_ok());
+        assert!(matches!(
+            check_fee(fee, Some(fee - 1)),
+            Err(DaError::FeeAboveMax { estimated_fee, max_fee }) if estimated_fee == fee && max_fee == fee - 1
+        ));
     }
 }
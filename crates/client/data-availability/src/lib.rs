@@ -11,6 +11,7 @@ mod da_metrics;
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::marker::PhantomData;
+use std::path::Path;
 use std::sync::Arc;
 use std::time;
 
@@ -62,6 +63,10 @@ pub enum DaError {
     FailedDataValidation(anyhow::Error),
     #[error("Invalid http endpoint: {0}")]
     InvalidHttpEndpoint(String),
+    #[error("failed resolving environment variable: {0}")]
+    FailedResolvingEnvVar(String),
+    #[error("estimated fee {estimated_fee} exceeds the max fee {max_fee}")]
+    FeeAboveMax { estimated_fee: U256, max_fee: U256 },
 }
 
 impl Display for DaLayer {
@@ -125,6 +130,57 @@ pub trait DaClient: Send + Sync {
     async fn last_published_state(&self) -> Result<I256>;
     async fn publish_state_diff(&self, state_diff: Vec<U256>) -> Result<()>;
     fn get_da_metric_labels(&self) -> HashMap<String, String>;
+
+    /// Number of the last block whose state diff has been published
+    ///
+    /// Fails while `last_published_state` is negative, meaning nothing has been published yet.
+    async fn last_published_block_number(&self) -> Result<u64> {
+        let last_published_state = self.last_published_state().await?;
+        let block_number = u64::try_from(last_published_state).map_err(|e| {
+            DaError::FailedConversion(anyhow!("invalid last published state {last_published_state}: {e}"))
+        })?;
+
+        Ok(block_number)
+    }
+
+    /// Estimated cost of publishing `state_diff`, in the smallest fee unit of the DA layer
+    ///
+    /// Layers that can't estimate it return an error.
+    async fn estimate_publish_cost(&self, _state_diff: &[U256]) -> Result<U256> {
+        Err(unsupported_by_da_layer("publish cost estimation"))
+    }
+}
+
+/// Error returned for a [`DaClient`] operation the DA layer can't serve
+pub(crate) fn unsupported_by_da_layer(operation: &str) -> anyhow::Error {
+    DaError::FailedDataFetching(anyhow!("{operation} is not supported by this DA layer")).into()
+}
+
+/// Build the client of the given DA layer from the config file at `da_path`
+///
+/// The file is parsed as the config of the selected layer, so the DA mode is chosen
+/// independently of the layer through the config `mode` field.
+pub fn create_da_client(da_layer: DaLayer, da_path: &Path) -> Result<Box<dyn DaClient + Send + Sync>, DaError> {
+    let da_path = da_path.to_path_buf();
+
+    let da_client: Box<dyn DaClient + Send + Sync> = match da_layer {
+        #[cfg(feature = "celestia")]
+        DaLayer::Celestia => {
+            let celestia_conf = celestia::config::CelestiaConfig::try_from(&da_path)?;
+            Box::new(celestia::CelestiaClient::try_from(celestia_conf)?)
+        }
+        DaLayer::Ethereum => {
+            let ethereum_conf = ethereum::config::EthereumConfig::try_from(&da_path)?;
+            Box::new(ethereum::EthereumClient::try_from(ethereum_conf)?)
+        }
+        #[cfg(feature = "avail")]
+        DaLayer::Avail => {
+            let avail_conf = avail::config::AvailConfig::try_from(&da_path)?;
+            Box::new(avail::AvailClient::try_from(avail_conf)?)
+        }
+    };
+
+    Ok(da_client)
 }
 
 /// The client worker for DA related tasks
@@ -243,13 +299,12 @@ pub async fn update_state<B: BlockT, H: HasherT>(
     // store the state diff
     madara_backend.da().store_state_diff(&block_hash, &block_da_data.state_diff).map_err(|e| anyhow!("{e}"))?;
 
-    // Query last written state
-    // TODO: this value will be used to ensure the correct state diff is being written in
-    // Validity mode
-    let _last_published_state = da_client.last_published_state().await?;
-
     match da_client.get_mode() {
         DaMode::Validity => {
+            // Query last written state
+            // TODO: this value will be used to ensure the correct state diff is being written
+            let _last_published_state = da_client.last_published_state().await?;
+
             // Check the SHARP status of last_proved + 1
             // Write the publish state diff of last_proved + 1
             log::info!("[VALIDITY] not implemented");
@@ -263,3 +318,81 @@ pub async fn update_state<B: BlockT, H: HasherT>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::ethereum::config::EthereumConfig;
+
+    struct MockDaClient {
+        last_published_state: I256,
+    }
+
+    #[async_trait]
+    impl DaClient for MockDaClient {
+        fn get_mode(&self) -> DaMode {
+            DaMode::Sovereign
+        }
+
+        async fn last_published_state(&self) -> Result<I256> {
+            Ok(self.last_published_state)
+        }
+
+        async fn publish_state_diff(&self, _state_diff: Vec<U256>) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_da_metric_labels(&self) -> HashMap<String, String> {
+            HashMap::new()
+        }
+    }
+
+    #[rstest]
+    #[case(0, Some(0))]
+    #[case(42, Some(42))]
+    #[case(-1, None)]
+    #[tokio::test]
+    async fn last_published_block_number_works(#[case] last_published_state: i64, #[case] expected: Option<u64>) {
+        let da_client = MockDaClient { last_published_state: I256::from(last_published_state) };
+        assert_eq!(da_client.last_published_block_number().await.ok(), expected);
+    }
+
+    fn create_da_client_with_config(
+        da_layer: DaLayer,
+        config: &str,
+    ) -> Result<Box<dyn DaClient + Send + Sync>, DaError> {
+        let da_path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&da_path, config).unwrap();
+
+        let da_client = create_da_client(da_layer, &da_path);
+        std::fs::remove_file(&da_path).unwrap();
+
+        da_client
+    }
+
+    #[test]
+    fn create_da_client_selects_ethereum() {
+        let config = serde_json::to_string(&EthereumConfig::default()).unwrap();
+        let da_client = create_da_client_with_config(DaLayer::Ethereum, &config).unwrap();
+
+        assert_eq!(da_client.get_da_metric_labels().get("name").map(String::as_str), Some("ethereum"));
+        assert_eq!(da_client.get_mode(), DaMode::Sovereign);
+    }
+
+    #[cfg(feature = "celestia")]
+    #[tokio::test]
+    async fn create_da_client_selects_celestia() {
+        let da_client = create_da_client_with_config(DaLayer::Celestia, r#"{ "mode": "validity" }"#).unwrap();
+
+        assert_eq!(da_client.get_da_metric_labels().get("name").map(String::as_str), Some("celestia"));
+        assert_eq!(da_client.get_mode(), DaMode::Validity);
+    }
+
+    #[test]
+    fn create_da_client_fails_without_config() {
+        let da_path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+        assert!(matches!(create_da_client(DaLayer::Ethereum, &da_path), Err(DaError::FailedOpeningConfig(_))));
+    }
+}
@@ -4,6 +4,8 @@ use starknet_api::api_core::{Nonce, PatriciaKey};
 use starknet_api::hash::StarkFelt;
 use url::{ParseError, Url};
 
+use crate::DaError;
+
 const CLASS_FLAG_TRUE: &str = "0x100000000000000000000000000000001"; // 2 ^ 128 + 1
 const NONCE_BASE: &str = "0xFFFFFFFFFFFFFFFF"; // 2 ^ 64 - 1
 
@@ -113,6 +115,16 @@ pub fn get_valid_url(endpoint: &str) -> Result<Url, ParseError> {
     Url::parse(endpoint)
 }
 
+/// Host (and port if explicit) of an endpoint, leaving out the path and query which may hold api
+/// keys. Used to label metrics.
+pub fn endpoint_host(url: &Url) -> String {
+    match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => String::new(),
+    }
+}
+
 pub fn is_valid_ws_endpoint(endpoint: &str) -> bool {
     if let Ok(url) = get_valid_url(endpoint) { matches!(url.scheme(), "ws" | "wss") } else { false }
 }
@@ -121,6 +133,29 @@ pub fn is_valid_http_endpoint(endpoint: &str) -> bool {
     if let Ok(url) = get_valid_url(endpoint) { matches!(url.scheme(), "http" | "https") } else { false }
 }
 
+/// Replaces every `${ENV_VAR}` occurrence in `value` with the content of the environment variable
+///
+/// Used for config fields holding secrets (keys, authenticated urls) so they don't have to be
+/// stored on disk. Fails if a referenced variable is not set or if a placeholder is not closed.
+pub fn resolve_env_vars(value: &str) -> Result<String, DaError> {
+    let mut resolved = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        resolved.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| DaError::FailedResolvingEnvVar(format!("unterminated placeholder in `{value}`")))?;
+        let name = &rest[start + 2..start + end];
+        let var = std::env::var(name).map_err(|e| DaError::FailedResolvingEnvVar(format!("{name}: {e}")))?;
+        resolved.push_str(&var);
+        rest = &rest[start + end + 1..];
+    }
+    resolved.push_str(rest);
+
+    Ok(resolved)
+}
+
 pub fn safe_split(key: &[u8]) -> ([u8; 16], Option<Vec<u8>>) {
     let length = key.len();
     let (mut child, mut rest) = ([0_u8; 16], None);
@@ -171,4 +206,31 @@ mod tests {
         let expected = U256::from_str_radix(&expected, 10).unwrap();
         assert_eq!(da_word, expected);
     }
+
+    #[rstest]
+    #[case("http://127.0.0.1:8545", "127.0.0.1:8545")]
+    #[case("https://eth.node/v2/secret-key?token=secret", "eth.node")]
+    fn endpoint_host_works(#[case] endpoint: &str, #[case] expected: &str) {
+        assert_eq!(endpoint_host(&get_valid_url(endpoint).unwrap()), expected);
+    }
+
+    #[test]
+    fn resolve_env_vars_works() {
+        std::env::set_var("MADARA_DA_TEST_KEY", "secret");
+        std::env::set_var("MADARA_DA_TEST_HOST", "eth.node");
+
+        assert_eq!(resolve_env_vars("no placeholder").unwrap(), "no placeholder");
+        assert_eq!(resolve_env_vars("${MADARA_DA_TEST_KEY}").unwrap(), "secret");
+        assert_eq!(
+            resolve_env_vars("https://${MADARA_DA_TEST_HOST}/v2/${MADARA_DA_TEST_KEY}").unwrap(),
+            "https://eth.node/v2/secret"
+        );
+    }
+
+    #[rstest]
+    #[case("${MADARA_DA_TEST_UNSET}")]
+    #[case("https://eth.node/v2/${MADARA_DA_TEST_KEY")]
+    fn resolve_env_vars_fails(#[case] value: &str) {
+        assert!(matches!(resolve_env_vars(value), Err(DaError::FailedResolvingEnvVar(_))));
+    }
 }
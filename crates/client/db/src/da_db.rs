@@ -11,16 +11,24 @@ use uuid::Uuid;
 
 use crate::{DbError, DbHash};
 
+/// Prefix of state diffs stored SCALE encoded then zstd compressed
+///
+/// Entries without it were stored uncompressed and are still decoded as plain SCALE.
+const STATE_DIFF_ZSTD_V1: u8 = 1;
+/// Magic number starting every zstd frame
+const ZSTD_MAGIC_NUMBER: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
 // The fact db stores DA facts that need to be written to L1
 pub struct DaDb {
     pub(crate) db: Arc<dyn Database<DbHash>>,
+    pub(crate) state_diff_compression_level: i32,
 }
 
 // TODO: purge old cairo job keys
 impl DaDb {
     pub fn state_diff(&self, block_hash: &BlockHash) -> Result<ThinStateDiff, DbError> {
         match self.db.get(crate::columns::DA, block_hash.0.bytes()) {
-            Some(raw) => Ok(ThinStateDiff::decode(&mut &raw[..])?),
+            Some(raw) => decode_state_diff(&raw),
             None => Err(DbError::ValueNotInitialized(crate::columns::DA, block_hash.to_string())),
         }
     }
@@ -28,7 +36,12 @@ impl DaDb {
     pub fn store_state_diff(&self, block_hash: &BlockHash, diff: &ThinStateDiff) -> Result<(), DbError> {
         let mut transaction = sp_database::Transaction::new();
 
-        transaction.set(crate::columns::DA, block_hash.0.bytes(), &diff.encode());
+        let mut value = vec![STATE_DIFF_ZSTD_V1];
+        value.extend(
+            zstd::encode_all(&diff.encode()[..], self.state_diff_compression_level)
+                .map_err(DbError::CompressionError)?,
+        );
+        transaction.set(crate::columns::DA, block_hash.0.bytes(), &value);
 
         self.db.commit(transaction)?;
 
@@ -76,3 +89,74 @@ impl DaDb {
         Ok(())
     }
 }
+
+fn decode_state_diff(raw: &[u8]) -> Result<ThinStateDiff, DbError> {
+    match raw.split_first() {
+        Some((&STATE_DIFF_ZSTD_V1, compressed)) if compressed.starts_with(&ZSTD_MAGIC_NUMBER) => {
+            let encoded = zstd::decode_all(compressed).map_err(DbError::CompressionError)?;
+            Ok(ThinStateDiff::decode(&mut &encoded[..])?)
+        }
+        _ => Ok(ThinStateDiff::decode(&mut &raw[..])?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sp_database::MemDb;
+    use starknet_api::api_core::{ContractAddress, PatriciaKey};
+    use starknet_api::state::StorageKey;
+
+    use super::*;
+
+    fn large_state_diff() -> ThinStateDiff {
+        let key = |i: u64| PatriciaKey::try_from(StarkFelt::from(i)).unwrap();
+        ThinStateDiff {
+            storage_diffs: (0..100)
+                .map(|i| {
+                    (ContractAddress(key(i)), (0..100).map(|j| (StorageKey(key(j)), StarkFelt::from(1_u64))).collect())
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn state_diff_is_stored_compressed() {
+        let da = DaDb { db: Arc::new(MemDb::default()), state_diff_compression_level: zstd::DEFAULT_COMPRESSION_LEVEL };
+        let block_hash = BlockHash(StarkFelt::from(1_u64));
+        let diff = large_state_diff();
+
+        da.store_state_diff(&block_hash, &diff).unwrap();
+
+        let raw = da.db.get(crate::columns::DA, block_hash.0.bytes()).unwrap();
+        assert!(raw.len() < diff.encode().len());
+        assert_eq!(da.state_diff(&block_hash).unwrap(), diff);
+    }
+
+    #[test]
+    fn corrupted_compressed_state_diff_fails() {
+        let da = DaDb { db: Arc::new(MemDb::default()), state_diff_compression_level: zstd::DEFAULT_COMPRESSION_LEVEL };
+        let block_hash = BlockHash(StarkFelt::from(1_u64));
+
+        da.store_state_diff(&block_hash, &large_state_diff()).unwrap();
+        let raw = da.db.get(crate::columns::DA, block_hash.0.bytes()).unwrap();
+        let mut transaction = sp_database::Transaction::new();
+        transaction.set(crate::columns::DA, block_hash.0.bytes(), &raw[..raw.len() / 2]);
+        da.db.commit(transaction).unwrap();
+
+        assert!(matches!(da.state_diff(&block_hash), Err(DbError::CompressionError(_))));
+    }
+
+    #[test]
+    fn uncompressed_state_diff_is_still_readable() {
+        let da = DaDb { db: Arc::new(MemDb::default()), state_diff_compression_level: zstd::DEFAULT_COMPRESSION_LEVEL };
+        let block_hash = BlockHash(StarkFelt::from(1_u64));
+        let diff = large_state_diff();
+
+        let mut transaction = sp_database::Transaction::new();
+        transaction.set(crate::columns::DA, block_hash.0.bytes(), &diff.encode());
+        da.db.commit(transaction).unwrap();
+
+        assert_eq!(da.state_diff(&block_hash).unwrap(), diff);
+    }
+}
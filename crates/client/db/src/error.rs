@@ -4,6 +4,8 @@ pub enum DbError {
     CommitError(#[from] sp_database::error::DatabaseError),
     #[error("Failed to deserialize DB Data: `{0}`")]
     DeserializeError(#[from] parity_scale_codec::Error),
+    #[error("Failed to (de)compress DB Data: `{0}`")]
+    CompressionError(std::io::Error),
     #[error("Failed to build Uuid: `{0}`")]
     Uuid(#[from] uuid::Error),
     #[error("A value was queryied that was not initialized at column: `{0}` key: `{1}`")]
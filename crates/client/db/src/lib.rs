@@ -134,13 +134,21 @@ impl<B: BlockT> Backend<B> {
         Ok(Self {
             mapping: Arc::new(MappingDb::new(db.clone(), cache_more_things)),
             meta: Arc::new(MetaDb { db: db.clone(), _marker: PhantomData }),
-            da: Arc::new(DaDb { db: db.clone() }),
+            da: Arc::new(DaDb { db: db.clone(), state_diff_compression_level: zstd::DEFAULT_COMPRESSION_LEVEL }),
             messaging: Arc::new(MessagingDb { db: db.clone() }),
             sierra_classes: Arc::new(SierraClassesDb { db: db.clone() }),
             l1_handler_paid_fee: Arc::new(L1HandlerTxFeeDb { db: db.clone() }),
         })
     }
 
+    /// Set the zstd compression level of the state diffs stored in the da database
+    ///
+    /// Defaults to `zstd::DEFAULT_COMPRESSION_LEVEL`. Only affects newly stored state diffs.
+    pub fn with_state_diff_compression_level(mut self, level: i32) -> Self {
+        self.da = Arc::new(DaDb { db: self.da.db.clone(), state_diff_compression_level: level });
+        self
+    }
+
     /// Return the mapping database manager
     pub fn mapping(&self) -> &Arc<MappingDb<B>> {
         &self.mapping
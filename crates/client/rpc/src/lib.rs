@@ -14,7 +14,8 @@ use std::sync::Arc;
 use errors::StarknetRpcApiError;
 use jsonrpsee::core::{async_trait, RpcResult};
 use jsonrpsee::types::error::CallError;
-use log::error;
+use log::{debug, error};
+use mc_db::DbError;
 use mc_genesis_data_provider::GenesisProvider;
 pub use mc_rpc_core::utils::*;
 pub use mc_rpc_core::{
@@ -208,10 +209,8 @@ where
     ///
     /// * `starknet_block_hash` - The hash of the block containing the state diff (starknet block).
     fn get_state_diff(&self, starknet_block_hash: &BlockHash) -> Result<StateDiff, StarknetRpcApiError> {
-        let state_diff = self.backend.da().state_diff(starknet_block_hash).map_err(|e| {
-            error!("Failed to retrieve state diff from cache for block with hash {}: {e}", starknet_block_hash);
-            StarknetRpcApiError::InternalServerError
-        })?;
+        let state_diff =
+            self.backend.da().state_diff(starknet_block_hash).map_err(|e| state_diff_error(starknet_block_hash, e))?;
 
         let rpc_state_diff = to_rpc_state_diff(state_diff);
 
@@ -1262,10 +1261,7 @@ where
 
         let starknet_block_hash = BlockHash(starknet_block.header().hash::<H>().into());
 
-        let state_diff = self.get_state_diff(&starknet_block_hash).map_err(|e| {
-            error!("Failed to get state diff. Starknet block hash: {starknet_block_hash}, error: {e}");
-            StarknetRpcApiError::InternalServerError
-        })?;
+        let state_diff = self.get_state_diff(&starknet_block_hash)?;
 
         Ok(StateUpdate {
             block_hash: starknet_block.header().hash::<H>().into(),
@@ -1730,3 +1726,113 @@ where
         },
     }
 }
+
+/// Maps a failure to read the state diff of a block from the da db to an rpc error
+///
+/// The state diff of a block is only stored once it went through the DA worker, a missing one is
+/// expected on nodes running without a DA layer and reported as an unknown block.
+fn state_diff_error(starknet_block_hash: &BlockHash, e: DbError) -> StarknetRpcApiError {
+    match e {
+        DbError::ValueNotInitialized(..) => {
+            debug!("No state diff stored for block with hash {starknet_block_hash}");
+            StarknetRpcApiError::BlockNotFound
+        }
+        _ => {
+            error!("Failed to retrieve state diff from cache for block with hash {starknet_block_hash}: {e}");
+            StarknetRpcApiError::InternalServerError
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use sc_client_db::DatabaseSource;
+    use sp_runtime::traits::BlakeTwo256;
+    use sp_runtime::{generic, OpaqueExtrinsic};
+    use starknet_api::api_core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+    use starknet_api::hash::StarkFelt;
+    use starknet_api::state::{StorageKey, ThinStateDiff};
+    use starknet_core::types::{
+        ContractStorageDiffItem, DeclaredClassItem, DeployedContractItem, NonceUpdate, ReplacedClassItem, StorageEntry,
+    };
+
+    use super::*;
+
+    type OpaqueBlock = generic::Block<generic::Header<u32, BlakeTwo256>, OpaqueExtrinsic>;
+
+    #[test]
+    fn state_diff_error_works() {
+        let block_hash = BlockHash(StarkFelt::from(1_u64));
+
+        assert!(matches!(
+            state_diff_error(&block_hash, DbError::ValueNotInitialized(0, block_hash.to_string())),
+            StarknetRpcApiError::BlockNotFound
+        ));
+        assert!(matches!(
+            state_diff_error(
+                &block_hash,
+                DbError::CompressionError(std::io::Error::new(std::io::ErrorKind::Other, "corrupted"))
+            ),
+            StarknetRpcApiError::InternalServerError
+        ));
+    }
+
+    #[test]
+    fn stored_state_diff_is_returned_as_rpc_state_diff() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backend = mc_db::Backend::<OpaqueBlock>::open(
+            &DatabaseSource::RocksDb { path: db_dir.path().into(), cache_size: 0 },
+            db_dir.path(),
+            false,
+        )
+        .unwrap();
+        let block_hash = BlockHash(StarkFelt::from(1_u64));
+        let address = ContractAddress(PatriciaKey::try_from(StarkFelt::from(2_u64)).unwrap());
+        let thin_state_diff = ThinStateDiff {
+            deployed_contracts: [(address, ClassHash(StarkFelt::from(3_u64)))].into_iter().collect(),
+            storage_diffs: [(
+                address,
+                [(StorageKey(PatriciaKey::try_from(StarkFelt::from(4_u64)).unwrap()), StarkFelt::from(5_u64))]
+                    .into_iter()
+                    .collect(),
+            )]
+            .into_iter()
+            .collect(),
+            declared_classes: [(ClassHash(StarkFelt::from(6_u64)), CompiledClassHash(StarkFelt::from(7_u64)))]
+                .into_iter()
+                .collect(),
+            deprecated_declared_classes: vec![ClassHash(StarkFelt::from(8_u64))],
+            nonces: [(address, Nonce(StarkFelt::from(9_u64)))].into_iter().collect(),
+            replaced_classes: [(address, ClassHash(StarkFelt::from(10_u64)))].into_iter().collect(),
+        };
+
+        backend.da().store_state_diff(&block_hash, &thin_state_diff).unwrap();
+        let state_diff = to_rpc_state_diff(backend.da().state_diff(&block_hash).unwrap());
+
+        let address = FieldElement::from(2_u64);
+        assert_eq!(
+            state_diff,
+            StateDiff {
+                storage_diffs: vec![ContractStorageDiffItem {
+                    address,
+                    storage_entries: vec![StorageEntry {
+                        key: FieldElement::from(4_u64),
+                        value: FieldElement::from(5_u64)
+                    }],
+                }],
+                deprecated_declared_classes: vec![FieldElement::from(8_u64)],
+                declared_classes: vec![DeclaredClassItem {
+                    class_hash: FieldElement::from(6_u64),
+                    compiled_class_hash: FieldElement::from(7_u64),
+                }],
+                deployed_contracts: vec![DeployedContractItem { address, class_hash: FieldElement::from(3_u64) }],
+                replaced_classes: vec![ReplacedClassItem {
+                    contract_address: address,
+                    class_hash: FieldElement::from(10_u64),
+                }],
+                nonces: vec![NonceUpdate { contract_address: address, nonce: FieldElement::from(9_u64) }],
+            }
+        );
+    }
+}
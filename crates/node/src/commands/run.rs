@@ -1,15 +1,8 @@
-use std::fs::File;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 use clap::ValueHint::FilePath;
 use madara_runtime::SealingMode;
-#[cfg(feature = "avail")]
-use mc_data_availability::avail::{config::AvailConfig, AvailClient};
-#[cfg(feature = "celestia")]
-use mc_data_availability::celestia::{config::CelestiaConfig, CelestiaClient};
-use mc_data_availability::ethereum::config::EthereumConfig;
-use mc_data_availability::ethereum::EthereumClient;
-use mc_data_availability::{DaClient, DaLayer};
+use mc_data_availability::{create_da_client, DaLayer};
 use mc_l1_messages::config::{L1MessagesWorkerConfig, L1MessagesWorkerConfigError};
 use mc_settlement::SettlementLayer;
 use sc_cli::{Result, RpcMethods, RunCmd, SubstrateCli};
@@ -99,6 +92,13 @@ pub struct ExtendedRunCmd {
     #[clap(long, value_hint = FilePath, requires = "da_layer")]
     pub da_conf: Option<PathBuf>,
 
+    /// zstd compression level (1 to 22) of the state diffs stored for the DA layer
+    ///
+    /// Higher levels use less disk space but take longer to compress. Defaults to the zstd
+    /// default level.
+    #[clap(long, requires = "da_layer", value_parser = clap::value_parser!(i32).range(1..=22))]
+    pub state_diff_compression_level: Option<i32>,
+
     /// Choose a supported settlement layer
     #[clap(long, ignore_case = true)]
     pub settlement: Option<SettlementLayer>,
@@ -156,32 +156,6 @@ impl ExtendedRunCmd {
     }
 }
 
-fn init_da_client(da_layer: DaLayer, da_path: &Path) -> Result<Box<dyn DaClient + Send + Sync>> {
-    let file = File::open(da_path)?;
-
-    let da_client: Box<dyn DaClient + Send + Sync> = match da_layer {
-        #[cfg(feature = "celestia")]
-        DaLayer::Celestia => {
-            let celestia_conf: CelestiaConfig =
-                serde_json::from_reader(file).map_err(|e| sc_cli::Error::Input(e.to_string()))?;
-            Box::new(CelestiaClient::try_from(celestia_conf).map_err(|e| sc_cli::Error::Input(e.to_string()))?)
-        }
-        DaLayer::Ethereum => {
-            let ethereum_conf: EthereumConfig =
-                serde_json::from_reader(file).map_err(|e| sc_cli::Error::Input(e.to_string()))?;
-            Box::new(EthereumClient::try_from(ethereum_conf).map_err(|e| sc_cli::Error::Input(e.to_string()))?)
-        }
-        #[cfg(feature = "avail")]
-        DaLayer::Avail => {
-            let avail_conf: AvailConfig =
-                serde_json::from_reader(file).map_err(|e| sc_cli::Error::Input(e.to_string()))?;
-            Box::new(AvailClient::try_from(avail_conf).map_err(|e| sc_cli::Error::Input(e.to_string()))?)
-        }
-    };
-
-    Ok(da_client)
-}
-
 pub fn run_node(mut cli: Cli) -> Result<()> {
     if cli.run.base.shared_params.dev {
         override_dev_environment(&mut cli.run);
@@ -207,7 +181,7 @@ pub fn run_node(mut cli: Cli) -> Result<()> {
             };
 
             log::info!("Initializing DA client with layer: {:?}", da_layer);
-            Some(init_da_client(da_layer, &da_conf)?)
+            Some(create_da_client(da_layer, &da_conf).map_err(|e| sc_cli::Error::Input(e.to_string()))?)
         }
         None => {
             log::info!("Madara initialized w/o DA layer");
@@ -247,8 +221,16 @@ pub fn run_node(mut cli: Cli) -> Result<()> {
     runner.run_node_until_exit(|config| async move {
         let sealing = cli.run.sealing.map(Into::into).unwrap_or_default();
         let cache = cli.run.cache;
-        service::new_full(config, sealing, da_client, cache, l1_messages_worker_config, settlement_config)
-            .map_err(sc_cli::Error::Service)
+        service::new_full(
+            config,
+            sealing,
+            da_client,
+            cache,
+            cli.run.state_diff_compression_level,
+            l1_messages_worker_config,
+            settlement_config,
+        )
+        .map_err(sc_cli::Error::Service)
     })
 }
 
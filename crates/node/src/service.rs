@@ -83,6 +83,7 @@ pub fn new_partial<BIQ>(
     config: &Configuration,
     build_import_queue: BIQ,
     cache_more_things: bool,
+    state_diff_compression_level: Option<i32>,
 ) -> Result<
     sc_service::PartialComponents<
         FullClient,
@@ -172,7 +173,11 @@ where
         telemetry.as_ref().map(|x| x.handle()),
     )?;
 
-    let madara_backend = Arc::new(MadaraBackend::open(&config.database, &db_config_dir(config), cache_more_things)?);
+    let mut madara_backend = MadaraBackend::open(&config.database, &db_config_dir(config), cache_more_things)?;
+    if let Some(level) = state_diff_compression_level {
+        madara_backend = madara_backend.with_state_diff_compression_level(level);
+    }
+    let madara_backend = Arc::new(madara_backend);
 
     let (import_queue, block_import) = build_import_queue(
         client.clone(),
@@ -264,11 +269,14 @@ where
 /// # Arguments
 ///
 /// - `cache`: whether more information should be cached when storing the block in the database.
+/// - `state_diff_compression_level`: zstd level of the state diffs stored for the DA layer, the
+///   zstd default if `None`.
 pub fn new_full(
     config: Configuration,
     sealing: SealingMode,
     da_client: Option<Box<dyn DaClient + Send + Sync>>,
     cache_more_things: bool,
+    state_diff_compression_level: Option<i32>,
     l1_messages_worker_config: Option<L1MessagesWorkerConfig>,
     settlement_config: Option<(SettlementLayer, PathBuf)>,
 ) -> Result<TaskManager, ServiceError> {
@@ -284,7 +292,7 @@ pub fn new_full(
         select_chain,
         transaction_pool,
         other: (block_import, grandpa_link, mut telemetry, madara_backend),
-    } = new_partial(&config, build_import_queue, cache_more_things)?;
+    } = new_partial(&config, build_import_queue, cache_more_things, state_diff_compression_level)?;
 
     let mut net_config = sc_network::config::FullNetworkConfiguration::new(&config.network);
 
@@ -445,9 +453,8 @@ pub fn new_full(
     if let Some((layer_kind, config_path)) = settlement_config {
         let settlement_provider: Box<dyn SettlementProvider<_>> = match layer_kind {
             SettlementLayer::Ethereum => {
-                let file = std::fs::File::open(config_path)?;
-                let ethereum_conf: EthereumConfig =
-                    serde_json::from_reader(file).map_err(|e| ServiceError::Other(e.to_string()))?;
+                let ethereum_conf =
+                    EthereumConfig::try_from(&config_path).map_err(|e| ServiceError::Other(e.to_string()))?;
                 Box::new(
                     StarknetContractClient::try_from(ethereum_conf).map_err(|e| ServiceError::Other(e.to_string()))?,
                 )
@@ -702,6 +709,6 @@ type ChainOpsResult =
 pub fn new_chain_ops(config: &mut Configuration, cache_more_things: bool) -> ChainOpsResult {
     config.keystore = sc_service::config::KeystoreConfig::InMemory;
     let sc_service::PartialComponents { client, backend, import_queue, task_manager, other, .. } =
-        new_partial::<_>(config, build_aura_grandpa_import_queue, cache_more_things)?;
+        new_partial::<_>(config, build_aura_grandpa_import_queue, cache_more_things, None)?;
     Ok((client, backend, import_queue, task_manager, other.3))
 }
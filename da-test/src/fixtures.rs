@@ -5,9 +5,12 @@ use rstest::fixture;
 use crate::utils::get_da_client;
 
 #[fixture]
-pub fn da_client() -> Box<dyn DaClient + Send + Sync> {
+pub fn da_layer() -> DaLayer {
     let da_layer_str = std::env::var("DA_LAYER").expect("DA_LAYER env var not set");
-    let da_layer = DaLayer::from_str(&da_layer_str, true).expect("Invalid value for DA_LAYER");
+    DaLayer::from_str(&da_layer_str, true).expect("Invalid value for DA_LAYER")
+}
 
+#[fixture]
+pub fn da_client(da_layer: DaLayer) -> Box<dyn DaClient + Send + Sync> {
     get_da_client(da_layer)
 }